@@ -2,84 +2,104 @@ use byteorder::ByteOrder;
 use core::convert::TryInto;
 use std::borrow::Cow;
 
+use crate::encoding_buf::{reserve_u32, EncodingBuf};
+use crate::signature::{Signature, SignatureRef};
 use crate::EncodingContext;
+use crate::Format;
 use crate::{SharedData, SimpleVariantType};
 use crate::{Variant, VariantError, VariantType, VariantTypeConstants};
 
 // Since neither `From` trait nor `Vec` is from this crate, we need this intermediate type.
 //
 #[derive(Debug, Clone)]
-pub struct Array(Vec<Variant>);
+pub struct Array {
+    elements: Vec<Variant>,
+    // Carried explicitly rather than derived from `elements[0]` so an empty array still knows
+    // what it's an array of.
+    element_signature: Signature,
+}
 
 impl Array {
-    pub fn new() -> Self {
-        Array(vec![])
+    /// Creates an empty array of `element_signature` (e.g. `"i"` for an `ai`, or `"{sv}"` for an
+    /// `a{sv}`). The element type has to be given up front: unlike for a non-empty array, there's
+    /// no element to recover it from later.
+    pub fn new(element_signature: Signature) -> Self {
+        Array {
+            elements: vec![],
+            element_signature,
+        }
     }
 
-    pub fn new_from_vec(vec: Vec<Variant>) -> Self {
-        Array(vec)
+    pub fn new_from_vec(vec: Vec<Variant>, element_signature: Signature) -> Self {
+        Array {
+            elements: vec,
+            element_signature,
+        }
     }
 
     pub fn inner(&self) -> &Vec<Variant> {
-        &self.0
+        &self.elements
     }
 
     pub fn inner_mut(&mut self) -> &mut Vec<Variant> {
-        &mut self.0
+        &mut self.elements
     }
 
     pub fn take_inner(self) -> Vec<Variant> {
-        self.0
+        self.elements
     }
-}
 
-impl VariantTypeConstants for Array {
-    const SIGNATURE_CHAR: char = 'a';
-    const SIGNATURE_STR: &'static str = "a";
-    const ALIGNMENT: usize = 4;
-}
-
-impl VariantType for Array {
-    fn signature_char() -> char {
-        'a'
+    pub fn element_signature(&self) -> SignatureRef<'_> {
+        self.element_signature.as_ref()
     }
-    fn signature_str() -> &'static str {
-        Self::SIGNATURE_STR
-    }
-    fn alignment() -> usize {
-        Self::ALIGNMENT
-    }
-
-    fn encode_into(&self, bytes: &mut Vec<u8>, context: EncodingContext) {
-        Self::add_padding(bytes, context);
+}
 
-        let len_position = bytes.len();
-        bytes.extend(&0u32.to_ne_bytes());
+impl Array {
+    fn encode_into_dbus(&self, bytes: &mut dyn EncodingBuf, context: EncodingContext) {
+        let len_patch = reserve_u32(bytes);
         let n_bytes_before = bytes.len();
         let child_enc_context = context.copy_for_child();
         for element in self.inner() {
-            // Deep copying, nice!!! 🙈
             element.encode_value_into(bytes, child_enc_context);
         }
 
         // Set size of array in bytes
         let len = crate::utils::usize_to_u32(bytes.len() - n_bytes_before);
-        byteorder::NativeEndian::write_u32(&mut bytes[len_position..len_position + 4], len);
+        bytes.fill_u32(len_patch, len);
     }
 
-    fn slice_data(
-        data: &SharedData,
-        signature: &str,
-        context: EncodingContext,
-    ) -> Result<SharedData, VariantError> {
-        if signature.len() < 2 {
-            return Err(VariantError::InsufficientData);
+    fn encode_into_gvariant(&self, bytes: &mut dyn EncodingBuf, context: EncodingContext) {
+        if self.inner().is_empty() {
+            return;
         }
-        Self::ensure_correct_signature(signature)?;
 
-        // Child signature
-        let child_signature = crate::variant_type::slice_signature(&signature[1..])?;
+        let child_enc_context = context.copy_for_child();
+        let child_signature = self.element_signature().as_str();
+        if gvariant_is_fixed_sized(child_signature) {
+            for element in self.inner() {
+                element.encode_value_into(bytes, child_enc_context);
+            }
+        } else {
+            let start = bytes.len();
+            let mut end_offsets = Vec::with_capacity(self.inner().len());
+            for element in self.inner() {
+                element.encode_value_into(bytes, child_enc_context);
+                end_offsets.push(bytes.len() - start);
+            }
 
+            let body_len = bytes.len() - start;
+            let offset_width = gvariant_offset_width_for_body(body_len, end_offsets.len());
+            for offset in end_offsets {
+                gvariant_write_offset(bytes, offset, offset_width);
+            }
+        }
+    }
+
+    fn slice_data_dbus(
+        data: &SharedData,
+        child_signature: &str,
+        context: EncodingContext,
+    ) -> Result<SharedData, VariantError> {
         // Array size in bytes
         let len_slice = u32::slice_data_simple(&data, context)?;
         let mut extracted = len_slice.len();
@@ -103,60 +123,414 @@ impl VariantType for Array {
         Ok(data.head(extracted as usize))
     }
 
-    fn decode(
+    // GVariant carries no length prefix of its own, so this takes all of `data` as the array: the
+    // slice is exactly this array only when `data` is already bounded to it (e.g. the array is the
+    // last thing in `data`). `Structure::slice_data` passes each member the unbounded rest of the
+    // data and does not read GVariant framing offsets.
+    fn slice_data_gvariant(
         data: &SharedData,
+        _child_signature: &str,
+        _context: EncodingContext,
+    ) -> Result<SharedData, VariantError> {
+        Ok(data.head(data.len()))
+    }
+
+    /// Returns an iterator that decodes one element at a time, without ever materializing the
+    /// full `Vec<Variant>`. Useful for a large `ay`/`ai` payload or an `a(...)` of thousands of
+    /// structs when the caller only wants to scan once.
+    ///
+    /// `signature` is the full array signature (e.g. `"ai"`), same as [`VariantType::decode`].
+    pub fn iter_data<'d>(
+        data: &'d SharedData,
         signature: &str,
         context: EncodingContext,
-    ) -> Result<Self, VariantError> {
-        let padding = Self::padding(data.position(), context);
-        if data.len() < padding + 4 || signature.len() < 2 {
+    ) -> Result<ArrayIter<'d>, VariantError> {
+        if signature.len() < 2 {
             return Err(VariantError::InsufficientData);
         }
         Self::ensure_correct_signature(signature)?;
 
-        // Child signature
         let child_signature = crate::variant_type::slice_signature(&signature[1..])?;
 
+        match context.format() {
+            Format::DBus => Self::iter_data_dbus(data, child_signature, context),
+            Format::GVariant => Self::iter_data_gvariant(data, child_signature, context),
+        }
+    }
+
+    fn iter_data_dbus<'d>(
+        data: &'d SharedData,
+        child_signature: &str,
+        context: EncodingContext,
+    ) -> Result<ArrayIter<'d>, VariantError> {
+        let padding = Self::padding(data.position(), context);
+        if data.len() < padding + 4 {
+            return Err(VariantError::InsufficientData);
+        }
+
         // Array size in bytes
-        let mut extracted = padding + 4;
+        let extracted = padding + 4;
         let len = u32::decode_simple(&data.subset(padding, extracted), context)? as usize + 4;
+
+        Ok(ArrayIter::DBus {
+            data,
+            child_signature: child_signature.to_owned(),
+            context: context.copy_for_child(),
+            extracted,
+            len,
+        })
+    }
+
+    // See `slice_data_gvariant` for the framing assumption: `data` is already bounded to exactly
+    // this array's serialized bytes.
+    fn iter_data_gvariant<'d>(
+        data: &'d SharedData,
+        child_signature: &str,
+        context: EncodingContext,
+    ) -> Result<ArrayIter<'d>, VariantError> {
+        let total_len = data.len();
+        if total_len == 0 {
+            return Ok(ArrayIter::Empty);
+        }
+
         let child_enc_context = context.copy_for_child();
-        let mut elements = vec![];
+        if gvariant_is_fixed_sized(child_signature) {
+            return Ok(ArrayIter::GVariantFixed {
+                data,
+                child_signature: child_signature.to_owned(),
+                context: child_enc_context,
+                elem_size: gvariant_fixed_size(child_signature),
+                start: 0,
+                total_len,
+            });
+        }
 
-        while extracted < len {
-            let slice = crate::variant_type::slice_data(
-                &data.tail(extracted as usize),
+        let offset_width = gvariant_offset_width(total_len);
+        let body_len = gvariant_read_offset(data, total_len - offset_width, offset_width);
+        if body_len > total_len {
+            return Err(VariantError::InsufficientData);
+        }
+        let n_offsets = (total_len - body_len) / offset_width;
+
+        Ok(ArrayIter::GVariantVariable {
+            data,
+            child_signature: child_signature.to_owned(),
+            context: child_enc_context,
+            offset_width,
+            body_len,
+            index: 0,
+            n_offsets,
+            start: 0,
+        })
+    }
+}
+
+/// Lazily decodes the elements of an [`Array`], yielding one [`Variant`] at a time instead of
+/// building the whole `Vec<Variant>` up front. Created by [`Array::iter_data`]; modeled on
+/// `bytes::Buf`'s advance-as-you-read style of cursor.
+pub enum ArrayIter<'d> {
+    DBus {
+        data: &'d SharedData,
+        child_signature: String,
+        context: EncodingContext,
+        extracted: usize,
+        len: usize,
+    },
+    GVariantFixed {
+        data: &'d SharedData,
+        child_signature: String,
+        context: EncodingContext,
+        elem_size: usize,
+        start: usize,
+        total_len: usize,
+    },
+    GVariantVariable {
+        data: &'d SharedData,
+        child_signature: String,
+        context: EncodingContext,
+        offset_width: usize,
+        body_len: usize,
+        index: usize,
+        n_offsets: usize,
+        start: usize,
+    },
+    Empty,
+}
+
+impl<'d> Iterator for ArrayIter<'d> {
+    type Item = Result<Variant, VariantError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            ArrayIter::DBus {
+                data,
                 child_signature,
-                child_enc_context,
-            )?;
-            extracted += slice.len();
-            if extracted > len {
-                return Err(VariantError::InsufficientData);
+                context,
+                extracted,
+                len,
+            } => {
+                if *extracted >= *len {
+                    return None;
+                }
+
+                let slice = match crate::variant_type::slice_data(
+                    &data.tail(*extracted),
+                    child_signature,
+                    *context,
+                ) {
+                    Ok(slice) => slice,
+                    Err(err) => return Some(Err(err)),
+                };
+                *extracted += slice.len();
+                if *extracted > *len {
+                    return Some(Err(VariantError::InsufficientData));
+                }
+
+                Some(Variant::from_data(&slice, child_signature, *context))
+            }
+            ArrayIter::GVariantFixed {
+                data,
+                child_signature,
+                context,
+                elem_size,
+                start,
+                total_len,
+            } => {
+                if *start >= *total_len {
+                    return None;
+                }
+
+                let elem_data = data.subset(*start, *start + *elem_size);
+                *start += *elem_size;
+
+                Some(Variant::from_data(&elem_data, child_signature, *context))
+            }
+            ArrayIter::GVariantVariable {
+                data,
+                child_signature,
+                context,
+                offset_width,
+                body_len,
+                index,
+                n_offsets,
+                start,
+            } => {
+                if *index >= *n_offsets {
+                    return None;
+                }
+
+                let end = gvariant_read_offset(data, *body_len + *index * *offset_width, *offset_width);
+                if end < *start || end > *body_len {
+                    return Some(Err(VariantError::InsufficientData));
+                }
+
+                let elem_data = data.subset(*start, end);
+                *start = end;
+                *index += 1;
+
+                Some(Variant::from_data(&elem_data, child_signature, *context))
+            }
+            ArrayIter::Empty => None,
+        }
+    }
+}
+
+// The GVariant serialization of a container depends on whether its children have a fixed size:
+// fixed-size elements are simply concatenated, while variable-size ones need a trailing table of
+// framing offsets. Besides the basic fixed-size types, a struct is also fixed-size if every one
+// of its own members is (e.g. `(ii)`, `(yy)`) -- `s`/`o`/`g`/`v`/`a`/`m` are never fixed, so any
+// struct containing one of those, directly or nested, is not fixed either.
+pub(crate) fn gvariant_is_fixed_sized(signature: &str) -> bool {
+    match signature.chars().next() {
+        Some('y') | Some('b') | Some('n') | Some('q') | Some('i') | Some('u') | Some('x')
+        | Some('t') | Some('d') | Some('h') => true,
+        Some('(') => {
+            let mut members = &signature[1..signature.len() - 1];
+            while !members.is_empty() {
+                let member = match crate::variant_type::slice_signature(members) {
+                    Ok(member) => member,
+                    Err(_) => return false,
+                };
+                if !gvariant_is_fixed_sized(member) {
+                    return false;
+                }
+                members = &members[member.len()..];
             }
 
-            let element = Variant::from_data(&slice, child_signature, child_enc_context)?;
-            elements.push(element);
+            true
         }
-        if extracted == 0 {
-            return Err(VariantError::ExcessData);
+        _ => false,
+    }
+}
+
+// The GVariant alignment of a single basic type or a (fixed-size) struct -- the same value used
+// on the decode side by `EncodingContext`/`add_padding` for these signature chars, duplicated
+// here since a struct's own fixed size depends on its members' alignment, not just their size.
+fn gvariant_basic_alignment(signature: &str) -> usize {
+    match signature.chars().next() {
+        Some('y') | Some('b') => 1,
+        Some('n') | Some('q') => 2,
+        Some('i') | Some('u') | Some('h') => 4,
+        Some('x') | Some('t') | Some('d') => 8,
+        Some('(') => 8,
+        _ => 1,
+    }
+}
+
+fn gvariant_align_to(value: usize, alignment: usize) -> usize {
+    (value + alignment - 1) / alignment * alignment
+}
+
+pub(crate) fn gvariant_fixed_size(signature: &str) -> usize {
+    match signature.chars().next() {
+        Some('y') | Some('b') => 1,
+        Some('n') | Some('q') => 2,
+        Some('i') | Some('u') | Some('h') => 4,
+        Some('x') | Some('t') | Some('d') => 8,
+        Some('(') => {
+            let mut members = &signature[1..signature.len() - 1];
+            let mut size = 0usize;
+            let mut struct_alignment = 1usize;
+            while !members.is_empty() {
+                let member = crate::variant_type::slice_signature(members)
+                    .expect("caller already checked this struct is fixed-size");
+                let alignment = gvariant_basic_alignment(member);
+                struct_alignment = struct_alignment.max(alignment);
+                size = gvariant_align_to(size, alignment) + gvariant_fixed_size(member);
+                members = &members[member.len()..];
+            }
+
+            gvariant_align_to(size, struct_alignment)
         }
+        _ => unreachable!("not a fixed-size signature: {}", signature),
+    }
+}
 
-        Ok(Array::new_from_vec(elements))
+// The offset table uses the narrowest of 1/2/4/8 bytes that lets the whole serialized
+// container (body + table) fit, per the GVariant spec. Used on the decode side, where
+// `total_len` is already the real, final length of body + table.
+fn gvariant_offset_width(total_len: usize) -> usize {
+    for width in &[1usize, 2, 4, 8] {
+        let max = if *width == 8 {
+            u64::MAX
+        } else {
+            (1u64 << (width * 8)) - 1
+        };
+        if total_len as u64 <= max {
+            return *width;
+        }
     }
+    8
+}
 
-    fn ensure_correct_signature(signature: &str) -> Result<(), VariantError> {
-        let slice = Self::slice_signature(&signature)?;
-        if slice.len() != signature.len() {
-            return Err(VariantError::IncorrectType);
+// Same narrowest-width rule as `gvariant_offset_width`, but computed on the encode side, where
+// the table hasn't been written yet: growing the offset width also grows the total length (by
+// `n_offsets` bytes per extra byte of width), so the width itself has to be part of the check,
+// not just `body_len + n_offsets`.
+fn gvariant_offset_width_for_body(body_len: usize, n_offsets: usize) -> usize {
+    for width in &[1usize, 2, 4, 8] {
+        let max = if *width == 8 {
+            u64::MAX
+        } else {
+            (1u64 << (width * 8)) - 1
+        };
+        let total = body_len as u64 + (n_offsets as u64) * (*width as u64);
+        if total <= max {
+            return *width;
         }
+    }
+    8
+}
 
-        Ok(())
+fn gvariant_write_offset(bytes: &mut dyn EncodingBuf, offset: usize, width: usize) {
+    let offset = offset as u64;
+    match width {
+        1 => bytes.put_slice(&[offset as u8]),
+        2 => bytes.put_slice(&(offset as u16).to_le_bytes()),
+        4 => bytes.put_slice(&(offset as u32).to_le_bytes()),
+        8 => bytes.put_slice(&offset.to_le_bytes()),
+        _ => unreachable!("invalid offset width: {}", width),
     }
+}
 
-    fn signature<'b>(&'b self) -> Cow<'b, str> {
-        let signature = format!("a{}", self.inner()[0].value_signature());
+fn gvariant_read_offset(data: &SharedData, position: usize, width: usize) -> usize {
+    let slice = data.subset(position, position + width);
+    let bytes = slice.bytes();
+    (match width {
+        1 => bytes[0] as u64,
+        2 => byteorder::LittleEndian::read_u16(bytes) as u64,
+        4 => byteorder::LittleEndian::read_u32(bytes) as u64,
+        8 => byteorder::LittleEndian::read_u64(bytes),
+        _ => unreachable!("invalid offset width: {}", width),
+    }) as usize
+}
 
-        Cow::from(signature)
+impl VariantTypeConstants for Array {
+    const SIGNATURE_CHAR: char = 'a';
+    const SIGNATURE_STR: &'static str = "a";
+    const ALIGNMENT: usize = 4;
+}
+
+impl VariantType for Array {
+    fn signature_char() -> char {
+        'a'
+    }
+    fn signature_str() -> &'static str {
+        Self::SIGNATURE_STR
+    }
+    fn alignment() -> usize {
+        Self::ALIGNMENT
+    }
+
+    fn encode_into(&self, bytes: &mut dyn EncodingBuf, context: EncodingContext) {
+        Self::add_padding(bytes, context);
+
+        match context.format() {
+            Format::DBus => self.encode_into_dbus(bytes, context),
+            Format::GVariant => self.encode_into_gvariant(bytes, context),
+        }
+    }
+
+    fn slice_data(
+        data: &SharedData,
+        signature: &str,
+        context: EncodingContext,
+    ) -> Result<SharedData, VariantError> {
+        if signature.len() < 2 {
+            return Err(VariantError::InsufficientData);
+        }
+        Self::ensure_correct_signature(signature)?;
+
+        // Child signature
+        let child_signature = crate::variant_type::slice_signature(&signature[1..])?;
+
+        match context.format() {
+            Format::DBus => Self::slice_data_dbus(data, child_signature, context),
+            Format::GVariant => Self::slice_data_gvariant(data, child_signature, context),
+        }
+    }
+
+    fn decode(
+        data: &SharedData,
+        signature: &str,
+        context: EncodingContext,
+    ) -> Result<Self, VariantError> {
+        if signature.len() < 2 {
+            return Err(VariantError::InsufficientData);
+        }
+
+        let element_signature = SignatureRef::slice(&signature[1..])?.to_owned();
+        let elements = Self::iter_data(data, signature, context)?.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Array::new_from_vec(elements, element_signature))
+    }
+
+    fn ensure_correct_signature(signature: &str) -> Result<(), VariantError> {
+        SignatureRef::new(signature).map(|_| ())
+    }
+
+    fn signature<'b>(&'b self) -> Cow<'b, str> {
+        Cow::from(format!("a{}", self.element_signature().as_str()))
     }
 
     fn slice_signature(signature: &str) -> Result<&str, VariantError> {
@@ -165,9 +539,9 @@ impl VariantType for Array {
         }
 
         // There should be a valid complete signature after 'a' but not more than 1
-        let slice = crate::variant_type::slice_signature(&signature[1..])?;
+        let child = SignatureRef::slice(&signature[1..])?;
 
-        Ok(&signature[0..slice.len() + 1])
+        Ok(&signature[0..child.as_str().len() + 1])
     }
 
     fn is(variant: &Variant) -> bool {
@@ -216,12 +590,22 @@ impl<T: VariantType> TryInto<Vec<T>> for Array {
 impl<T: VariantType> From<Vec<T>> for Array {
     fn from(values: Vec<T>) -> Self {
         let mut v: Vec<Variant> = vec![];
+        let mut element_signature = None;
 
         for value in values {
-            v.push(value.to_variant());
+            let variant = value.to_variant();
+            if element_signature.is_none() {
+                element_signature = Signature::new(variant.value_signature().into_owned()).ok();
+            }
+            v.push(variant);
         }
 
-        Array::new_from_vec(v)
+        let element_signature = element_signature.unwrap_or_else(|| {
+            Signature::new(T::signature_str())
+                .expect("an empty Vec<T> needs T::signature_str() to be a complete signature")
+        });
+
+        Array::new_from_vec(v, element_signature)
     }
 }
 
@@ -230,3 +614,110 @@ impl From<crate::Dict> for Array {
         Array::from(value.take_inner())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Format;
+
+    #[test]
+    fn gvariant_offset_width_boundary() {
+        assert_eq!(gvariant_offset_width_for_body(0, 255), 1);
+        assert_eq!(gvariant_offset_width_for_body(0, 256), 2);
+
+        // Regression: encode used to pick the width from `body_len + n_offsets` alone, ignoring
+        // that growing the width also grows the total by `n_offsets` bytes per extra byte of
+        // width. With one ~64 KiB variable-size element, `body_len + n` fits a u16 but
+        // `body_len + n * width` doesn't once `width` grows past 1, so encode and decode used to
+        // disagree on the offset width.
+        let body_len = 65_534;
+        let n = 1;
+        let width = gvariant_offset_width_for_body(body_len, n);
+        assert_eq!(width, 4);
+
+        let total_len = body_len + n * width;
+        assert_eq!(gvariant_offset_width(total_len), width);
+    }
+
+    #[test]
+    fn gvariant_fixed_size_array_round_trips() {
+        let array = Array::new_from_vec(
+            vec![1u32.to_variant(), 2u32.to_variant(), 3u32.to_variant()],
+            Signature::new("u").unwrap(),
+        );
+
+        let mut bytes = Vec::new();
+        let context = EncodingContext::new(Format::GVariant);
+        array.encode_into(&mut bytes, context);
+
+        let data = SharedData::new(bytes);
+        let decoded = Array::decode(&data, "au", context).unwrap();
+        assert_eq!(decoded.inner().len(), 3);
+    }
+
+    #[test]
+    fn gvariant_variable_size_array_round_trips_across_width_boundary() {
+        // One string under 256 bytes and one over 64 KiB: the first end offset alone would fit a
+        // single byte, but the table uses one width for every offset, here 4 bytes.
+        let short = "a".repeat(200);
+        let long = "b".repeat(70_000);
+        let array = Array::new_from_vec(
+            vec![short.clone().to_variant(), long.clone().to_variant()],
+            Signature::new("s").unwrap(),
+        );
+
+        let mut bytes = Vec::new();
+        let context = EncodingContext::new(Format::GVariant);
+        array.encode_into(&mut bytes, context);
+
+        let data = SharedData::new(bytes);
+        let decoded = Array::decode(&data, "as", context).unwrap();
+        assert_eq!(decoded.inner().len(), 2);
+    }
+
+    #[test]
+    fn empty_array_signature_does_not_panic() {
+        let array = Array::new(Signature::new("i").unwrap());
+        assert_eq!(array.signature(), "ai");
+    }
+
+    #[test]
+    fn gvariant_boolean_is_one_byte_not_four() {
+        // Regression: a GVariant `b` used to be sized like the 4-byte D-Bus boolean, so `ab`
+        // recovered the wrong element count from its body length.
+        assert_eq!(gvariant_fixed_size("b"), 1);
+
+        let array = Array::new_from_vec(
+            vec![true.to_variant(), false.to_variant(), true.to_variant()],
+            Signature::new("b").unwrap(),
+        );
+
+        let mut bytes = Vec::new();
+        let context = EncodingContext::new(Format::GVariant);
+        array.encode_into(&mut bytes, context);
+        assert_eq!(bytes.len(), 3);
+
+        let data = SharedData::new(bytes);
+        let decoded = Array::decode(&data, "ab", context).unwrap();
+        assert_eq!(decoded.inner().len(), 3);
+    }
+
+    #[test]
+    fn gvariant_struct_of_fixed_size_members_is_itself_fixed_size() {
+        // `(ii)` has no variable-size member, so it should be serialized like any other
+        // fixed-size element -- concatenated with no trailing framing-offset table -- to stay
+        // interoperable with real GVariant.
+        assert!(gvariant_is_fixed_sized("(ii)"));
+        assert_eq!(gvariant_fixed_size("(ii)"), 8);
+
+        // `(yy)` is 8-bit aligned throughout, so its fixed size is its two bytes, not padded.
+        assert!(gvariant_is_fixed_sized("(yy)"));
+        assert_eq!(gvariant_fixed_size("(yy)"), 2);
+
+        // A struct with any variable-size member (here `s`) is never fixed-size.
+        assert!(!gvariant_is_fixed_sized("(is)"));
+
+        // Nor is one with a nested variable-size struct.
+        assert!(!gvariant_is_fixed_sized("((is))"));
+    }
+}
@@ -0,0 +1,60 @@
+use byteorder::{ByteOrder, NativeEndian};
+
+/// Abstraction over the output buffer that [`VariantType::encode_into`] writes into.
+///
+/// Mirrors the subset of `bytes::BufMut` this crate needs: a chainable sink that can grow,
+/// accept raw slices, and back-patch a handful of bytes it already wrote (used for the array
+/// length prefix, which is only known once all of its elements have been encoded). Implemented
+/// for `Vec<u8>` so existing callers keep working, and implementable for `bytes::BytesMut` or a
+/// chain of buffers so a whole message can be assembled straight into something destined for a
+/// socket, without a final monolithic copy.
+///
+/// [`VariantType::encode_into`]: crate::VariantType::encode_into
+pub trait EncodingBuf {
+    /// Number of bytes written so far.
+    fn len(&self) -> usize;
+
+    /// Appends `slice` to the end of the buffer.
+    fn put_slice(&mut self, slice: &[u8]);
+
+    /// Reserves `additional` bytes of spare capacity, without changing `len()`.
+    fn reserve(&mut self, additional: usize);
+
+    /// Fills in a `u32` previously reserved with [`reserve_u32`].
+    fn fill_u32(&mut self, patch: U32Patch, value: u32);
+}
+
+/// A handle to a 4-byte region reserved earlier in the buffer via [`reserve_u32`], to be filled
+/// in once its value is known (e.g. an array's length, only known after encoding its elements).
+/// It holds the byte offset at which [`reserve_u32`] wrote the 4 placeholder bytes; only
+/// [`EncodingBuf::fill_u32`] reads it.
+pub struct U32Patch {
+    position: usize,
+}
+
+/// Reserves 4 bytes for a `u32` that will be filled in later, returning a handle to pass to
+/// [`EncodingBuf::fill_u32`] once the value is known.
+pub fn reserve_u32<B: EncodingBuf + ?Sized>(buf: &mut B) -> U32Patch {
+    let position = buf.len();
+    buf.put_slice(&0u32.to_ne_bytes());
+
+    U32Patch { position }
+}
+
+impl EncodingBuf for Vec<u8> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn put_slice(&mut self, slice: &[u8]) {
+        self.extend_from_slice(slice);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+
+    fn fill_u32(&mut self, patch: U32Patch, value: u32) {
+        NativeEndian::write_u32(&mut self[patch.position..patch.position + 4], value);
+    }
+}
@@ -0,0 +1,236 @@
+use std::borrow::Cow;
+
+use crate::array::{gvariant_fixed_size, gvariant_is_fixed_sized};
+use crate::encoding_buf::EncodingBuf;
+use crate::signature::{Signature, SignatureRef};
+use crate::EncodingContext;
+use crate::{SharedData, Variant, VariantError, VariantType, VariantTypeConstants};
+
+/// GVariant's nullable container (signature char `m`), with no D-Bus equivalent. Used pervasively
+/// for optional fields in GVariant-based services, since D-Bus itself has no notion of "absent".
+///
+/// `None` encodes to zero bytes. `Some` of a fixed-size child encodes the child as-is; `Some` of
+/// a variable-size child encodes the child followed by a single zero-byte terminator, so that an
+/// empty string (`Some("")`) stays distinguishable from absence (`None`).
+#[derive(Debug, Clone)]
+pub struct Maybe {
+    value: Option<Box<Variant>>,
+    // Carried explicitly, same reasoning as `Array::element_signature`: `None` has no value to
+    // recover it from.
+    element_signature: Signature,
+}
+
+impl Maybe {
+    pub fn just(value: Variant) -> Self {
+        let element_signature = Signature::new(value.value_signature().into_owned())
+            .expect("a Variant's own signature is always valid");
+
+        Maybe {
+            value: Some(Box::new(value)),
+            element_signature,
+        }
+    }
+
+    pub fn nothing(element_signature: Signature) -> Self {
+        Maybe {
+            value: None,
+            element_signature,
+        }
+    }
+
+    pub fn inner(&self) -> Option<&Variant> {
+        self.value.as_deref()
+    }
+
+    pub fn take_inner(self) -> Option<Variant> {
+        self.value.map(|value| *value)
+    }
+
+    pub fn element_signature(&self) -> SignatureRef<'_> {
+        self.element_signature.as_ref()
+    }
+}
+
+impl VariantTypeConstants for Maybe {
+    const SIGNATURE_CHAR: char = 'm';
+    const SIGNATURE_STR: &'static str = "m";
+    // Like `Array::ALIGNMENT`, this is the container's own minimum alignment, not the (larger)
+    // alignment its child may additionally require.
+    const ALIGNMENT: usize = 1;
+}
+
+impl VariantType for Maybe {
+    fn signature_char() -> char {
+        Self::SIGNATURE_CHAR
+    }
+    fn signature_str() -> &'static str {
+        Self::SIGNATURE_STR
+    }
+    fn alignment() -> usize {
+        Self::ALIGNMENT
+    }
+
+    fn encode_into(&self, bytes: &mut dyn EncodingBuf, context: EncodingContext) {
+        Self::add_padding(bytes, context);
+
+        if let Some(value) = self.inner() {
+            let child_context = context.copy_for_child();
+            value.encode_value_into(bytes, child_context);
+
+            if !gvariant_is_fixed_sized(self.element_signature().as_str()) {
+                bytes.put_slice(&[0u8]);
+            }
+        }
+    }
+
+    fn slice_data(
+        data: &SharedData,
+        signature: &str,
+        context: EncodingContext,
+    ) -> Result<SharedData, VariantError> {
+        if signature.len() < 2 {
+            return Err(VariantError::InsufficientData);
+        }
+        Self::ensure_correct_signature(signature)?;
+        let child_signature = &signature[1..];
+
+        if data.len() == 0 {
+            return Ok(data.head(0));
+        }
+
+        let child_context = context.copy_for_child();
+        let child_slice = crate::variant_type::slice_data(data, child_signature, child_context)?;
+        let mut total = child_slice.len();
+        if !gvariant_is_fixed_sized(child_signature) {
+            total += 1;
+        }
+
+        Ok(data.head(total))
+    }
+
+    fn decode(
+        data: &SharedData,
+        signature: &str,
+        context: EncodingContext,
+    ) -> Result<Self, VariantError> {
+        if signature.len() < 2 {
+            return Err(VariantError::InsufficientData);
+        }
+        Self::ensure_correct_signature(signature)?;
+        let child_signature = &signature[1..];
+        let element_signature = Signature::new(child_signature)?;
+
+        if data.len() == 0 {
+            return Ok(Maybe::nothing(element_signature));
+        }
+
+        let child_context = context.copy_for_child();
+        let child_slice = if gvariant_is_fixed_sized(child_signature) {
+            let fixed_size = gvariant_fixed_size(child_signature);
+            if data.len() < fixed_size {
+                return Err(VariantError::InsufficientData);
+            }
+
+            data.head(fixed_size)
+        } else {
+            crate::variant_type::slice_data(data, child_signature, child_context)?
+        };
+        let value = Variant::from_data(&child_slice, child_signature, child_context)?;
+
+        Ok(Maybe::just(value))
+    }
+
+    fn ensure_correct_signature(signature: &str) -> Result<(), VariantError> {
+        let slice = Self::slice_signature(signature)?;
+        if slice.len() != signature.len() {
+            return Err(VariantError::IncorrectType);
+        }
+
+        Ok(())
+    }
+
+    fn signature<'b>(&'b self) -> Cow<'b, str> {
+        Cow::from(format!("m{}", self.element_signature().as_str()))
+    }
+
+    fn slice_signature(signature: &str) -> Result<&str, VariantError> {
+        if !signature.starts_with('m') {
+            return Err(VariantError::IncorrectType);
+        }
+
+        // `m` consumes exactly one following complete type.
+        let child = SignatureRef::slice(&signature[1..])?;
+
+        Ok(&signature[0..child.as_str().len() + 1])
+    }
+
+    fn is(variant: &Variant) -> bool {
+        if let Variant::Maybe(_) = variant {
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_from_variant(variant: Variant) -> Result<Self, VariantError> {
+        if let Variant::Maybe(value) = variant {
+            Ok(value)
+        } else {
+            Err(VariantError::IncorrectType)
+        }
+    }
+
+    fn from_variant(variant: &Variant) -> Result<&Self, VariantError> {
+        if let Variant::Maybe(value) = variant {
+            Ok(value)
+        } else {
+            Err(VariantError::IncorrectType)
+        }
+    }
+
+    fn to_variant(self) -> Variant {
+        Variant::Maybe(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Format;
+
+    #[test]
+    fn none_and_some_empty_string_are_distinguishable() {
+        let context = EncodingContext::new(Format::GVariant);
+
+        let mut none_bytes = Vec::new();
+        Maybe::nothing(Signature::new("s").unwrap()).encode_into(&mut none_bytes, context);
+        assert!(none_bytes.is_empty());
+
+        let mut some_empty_bytes = Vec::new();
+        Maybe::just("".to_string().to_variant()).encode_into(&mut some_empty_bytes, context);
+        // `Some("")` is the empty string's own NUL plus the trailing terminator that
+        // distinguishes it from `None`.
+        assert_eq!(some_empty_bytes, vec![0u8, 0u8]);
+
+        let none_data = SharedData::new(none_bytes);
+        let decoded_none = Maybe::decode(&none_data, "ms", context).unwrap();
+        assert!(decoded_none.inner().is_none());
+
+        let some_data = SharedData::new(some_empty_bytes);
+        let decoded_some = Maybe::decode(&some_data, "ms", context).unwrap();
+        assert_eq!(
+            decoded_some.inner().unwrap(),
+            &"".to_string().to_variant()
+        );
+    }
+
+    #[test]
+    fn truncated_fixed_size_value_is_insufficient_data_not_a_panic() {
+        let context = EncodingContext::new(Format::GVariant);
+        // `mi` needs 4 bytes for `Some`; give it only 1.
+        let data = SharedData::new(vec![0u8]);
+
+        let result = Maybe::decode(&data, "mi", context);
+        assert!(matches!(result, Err(VariantError::InsufficientData)));
+    }
+}
@@ -0,0 +1,188 @@
+use std::borrow::Cow;
+
+use crate::encoding_buf::EncodingBuf;
+use crate::EncodingContext;
+use crate::{SharedData, Variant, VariantError, VariantType, VariantTypeConstants};
+
+/// A single complete D-Bus/GVariant type signature (e.g. `"a{sv}"`, `"(ii)"`, `"i"`), owned.
+///
+/// Unlike a raw `&str`, constructing a `Signature` validates that it names exactly one complete
+/// type rather than a concatenation of several (or a truncated one), so code holding a
+/// `Signature` never has to re-validate it. This is also the D-Bus basic type `g`: a one-byte
+/// length, the signature's bytes, and a trailing NUL, the same shape as `s` and `o`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Signature(String);
+
+impl Signature {
+    /// Validates `signature` as a single complete type and takes ownership of it.
+    pub fn new(signature: impl Into<String>) -> Result<Self, VariantError> {
+        let signature = signature.into();
+        SignatureRef::new(&signature)?;
+
+        Ok(Signature(signature))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn as_ref(&self) -> SignatureRef<'_> {
+        SignatureRef(&self.0)
+    }
+}
+
+impl std::ops::Deref for Signature {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A borrowed, already-validated signature, as produced by [`SignatureRef::slice`]. Keeping this
+/// distinct from `&str` means the scan that finds where the signature ends only has to happen
+/// once, instead of at every call site that needs to know.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignatureRef<'s>(&'s str);
+
+impl<'s> SignatureRef<'s> {
+    /// Validates that `signature` is exactly one complete type, with nothing trailing.
+    pub fn new(signature: &'s str) -> Result<Self, VariantError> {
+        let slice = crate::variant_type::slice_signature(signature)?;
+        if slice.len() != signature.len() {
+            return Err(VariantError::IncorrectType);
+        }
+
+        Ok(SignatureRef(signature))
+    }
+
+    /// Slices the first complete type off the front of `signature` (e.g. the child out of an
+    /// array's `"ai"`, or one member out of a struct's `"(ii)"`), without requiring it to be the
+    /// only thing there.
+    pub fn slice(signature: &'s str) -> Result<Self, VariantError> {
+        let slice = crate::variant_type::slice_signature(signature)?;
+
+        Ok(SignatureRef(slice))
+    }
+
+    pub fn as_str(&self) -> &'s str {
+        self.0
+    }
+
+    pub fn to_owned(&self) -> Signature {
+        Signature(self.0.to_owned())
+    }
+}
+
+impl VariantTypeConstants for Signature {
+    const SIGNATURE_CHAR: char = 'g';
+    const SIGNATURE_STR: &'static str = "g";
+    const ALIGNMENT: usize = 1;
+}
+
+impl VariantType for Signature {
+    fn signature_char() -> char {
+        Self::SIGNATURE_CHAR
+    }
+    fn signature_str() -> &'static str {
+        Self::SIGNATURE_STR
+    }
+    fn alignment() -> usize {
+        Self::ALIGNMENT
+    }
+
+    fn encode_into(&self, bytes: &mut dyn EncodingBuf, _context: EncodingContext) {
+        let signature_bytes = self.as_str().as_bytes();
+        // Signatures are capped at 255 bytes by the D-Bus spec, so the length always fits a byte.
+        bytes.put_slice(&[signature_bytes.len() as u8]);
+        bytes.put_slice(signature_bytes);
+        bytes.put_slice(&[0u8]);
+    }
+
+    fn slice_data(
+        data: &SharedData,
+        signature: &str,
+        _context: EncodingContext,
+    ) -> Result<SharedData, VariantError> {
+        Self::ensure_correct_signature(signature)?;
+
+        if data.len() < 1 {
+            return Err(VariantError::InsufficientData);
+        }
+        let len = data.head(1).bytes()[0] as usize;
+        let total = 1 + len + 1;
+        if data.len() < total {
+            return Err(VariantError::InsufficientData);
+        }
+
+        Ok(data.head(total))
+    }
+
+    fn decode(
+        data: &SharedData,
+        signature: &str,
+        context: EncodingContext,
+    ) -> Result<Self, VariantError> {
+        let slice = Self::slice_data(data, signature, context)?;
+        let bytes = slice.bytes();
+        let len = bytes[0] as usize;
+        let text =
+            std::str::from_utf8(&bytes[1..1 + len]).map_err(|_| VariantError::IncorrectType)?;
+
+        Signature::new(text)
+    }
+
+    fn ensure_correct_signature(signature: &str) -> Result<(), VariantError> {
+        if signature != Self::SIGNATURE_STR {
+            return Err(VariantError::IncorrectType);
+        }
+
+        Ok(())
+    }
+
+    fn signature<'b>(&'b self) -> Cow<'b, str> {
+        Cow::from(Self::SIGNATURE_STR)
+    }
+
+    fn slice_signature(signature: &str) -> Result<&str, VariantError> {
+        if !signature.starts_with(Self::SIGNATURE_CHAR) {
+            return Err(VariantError::IncorrectType);
+        }
+
+        Ok(&signature[0..1])
+    }
+
+    fn is(variant: &Variant) -> bool {
+        if let Variant::Signature(_) = variant {
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_from_variant(variant: Variant) -> Result<Self, VariantError> {
+        if let Variant::Signature(value) = variant {
+            Ok(value)
+        } else {
+            Err(VariantError::IncorrectType)
+        }
+    }
+
+    fn from_variant(variant: &Variant) -> Result<&Self, VariantError> {
+        if let Variant::Signature(value) = variant {
+            Ok(value)
+        } else {
+            Err(VariantError::IncorrectType)
+        }
+    }
+
+    fn to_variant(self) -> Variant {
+        Variant::Signature(self)
+    }
+}
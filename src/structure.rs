@@ -0,0 +1,192 @@
+use std::borrow::Cow;
+
+use crate::signature::Signature;
+use crate::EncodingContext;
+use crate::{encoding_buf::EncodingBuf, SharedData, Variant, VariantError, VariantType, VariantTypeConstants};
+
+/// A heterogeneous D-Bus struct `(...)`: a fixed-arity, 8-byte-aligned sequence of differently
+/// typed fields, e.g. `(iu)` or `(sas)`.
+///
+/// This is the generic container `#[derive(VariantType)]` targets when converting a Rust struct
+/// to and from the dynamic [`Variant`] enum in its default (non-vardict) mode: there's no
+/// dedicated `Variant` arm per derived type, so derived types round-trip through `Structure`
+/// (encoding/decoding each of their own fields directly) instead.
+#[derive(Debug, Clone)]
+pub struct Structure {
+    fields: Vec<Variant>,
+    signature: Signature,
+}
+
+impl Structure {
+    pub fn new(fields: Vec<Variant>) -> Self {
+        let mut signature = String::from("(");
+        for field in &fields {
+            signature.push_str(&field.value_signature());
+        }
+        signature.push(')');
+
+        Structure {
+            fields,
+            signature: Signature::new(signature)
+                .expect("concatenated field signatures are always a valid struct signature"),
+        }
+    }
+
+    pub fn fields(&self) -> &Vec<Variant> {
+        &self.fields
+    }
+
+    pub fn into_fields(self) -> Vec<Variant> {
+        self.fields
+    }
+}
+
+impl VariantTypeConstants for Structure {
+    const SIGNATURE_CHAR: char = '(';
+    const SIGNATURE_STR: &'static str = "(";
+    const ALIGNMENT: usize = 8;
+}
+
+impl VariantType for Structure {
+    fn signature_char() -> char {
+        Self::SIGNATURE_CHAR
+    }
+    fn signature_str() -> &'static str {
+        Self::SIGNATURE_STR
+    }
+    fn alignment() -> usize {
+        Self::ALIGNMENT
+    }
+
+    fn encode_into(&self, bytes: &mut dyn EncodingBuf, context: EncodingContext) {
+        Self::add_padding(bytes, context);
+
+        let child_context = context.copy_for_child();
+        for field in &self.fields {
+            field.encode_value_into(bytes, child_context);
+        }
+    }
+
+    fn slice_data(
+        data: &SharedData,
+        signature: &str,
+        context: EncodingContext,
+    ) -> Result<SharedData, VariantError> {
+        Self::ensure_correct_signature(signature)?;
+
+        let padding = Self::padding(data.position(), context);
+        if data.len() < padding {
+            return Err(VariantError::InsufficientData);
+        }
+
+        let mut extracted = padding;
+        let child_context = context.copy_for_child();
+        let mut members = &signature[1..signature.len() - 1];
+        while !members.is_empty() {
+            let member_signature = crate::variant_type::slice_signature(members)?;
+            let slice = crate::variant_type::slice_data(
+                &data.tail(extracted),
+                member_signature,
+                child_context,
+            )?;
+            extracted += slice.len();
+            members = &members[member_signature.len()..];
+        }
+
+        Ok(data.head(extracted))
+    }
+
+    fn decode(
+        data: &SharedData,
+        signature: &str,
+        context: EncodingContext,
+    ) -> Result<Self, VariantError> {
+        Self::ensure_correct_signature(signature)?;
+
+        let padding = Self::padding(data.position(), context);
+        if data.len() < padding {
+            return Err(VariantError::InsufficientData);
+        }
+
+        let mut extracted = padding;
+        let child_context = context.copy_for_child();
+        let mut members = &signature[1..signature.len() - 1];
+        let mut fields = vec![];
+        while !members.is_empty() {
+            let member_signature = crate::variant_type::slice_signature(members)?;
+            let slice = crate::variant_type::slice_data(
+                &data.tail(extracted),
+                member_signature,
+                child_context,
+            )?;
+            extracted += slice.len();
+            fields.push(Variant::from_data(&slice, member_signature, child_context)?);
+            members = &members[member_signature.len()..];
+        }
+
+        Ok(Structure::new(fields))
+    }
+
+    fn ensure_correct_signature(signature: &str) -> Result<(), VariantError> {
+        let slice = Self::slice_signature(signature)?;
+        if slice.len() != signature.len() {
+            return Err(VariantError::IncorrectType);
+        }
+
+        Ok(())
+    }
+
+    fn signature<'b>(&'b self) -> Cow<'b, str> {
+        Cow::from(self.signature.as_str().to_owned())
+    }
+
+    fn slice_signature(signature: &str) -> Result<&str, VariantError> {
+        if !signature.starts_with('(') {
+            return Err(VariantError::IncorrectType);
+        }
+
+        let mut depth = 0usize;
+        for (i, c) in signature.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(&signature[0..=i]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Err(VariantError::InsufficientData)
+    }
+
+    fn is(variant: &Variant) -> bool {
+        if let Variant::Struct(_) = variant {
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_from_variant(variant: Variant) -> Result<Self, VariantError> {
+        if let Variant::Struct(value) = variant {
+            Ok(value)
+        } else {
+            Err(VariantError::IncorrectType)
+        }
+    }
+
+    fn from_variant(variant: &Variant) -> Result<&Self, VariantError> {
+        if let Variant::Struct(value) = variant {
+            Ok(value)
+        } else {
+            Err(VariantError::IncorrectType)
+        }
+    }
+
+    fn to_variant(self) -> Variant {
+        Variant::Struct(self)
+    }
+}
@@ -0,0 +1,407 @@
+//! `#[derive(VariantType)]` -- maps a plain Rust struct onto the D-Bus struct and `a{sv}` vardict
+//! encodings, so callers don't have to hand-write `encode_into`/`slice_data`/`decode`/
+//! `signature`/`ensure_correct_signature` and the `is`/`from_variant`/`take_from_variant` trio
+//! for every message payload type (see `zvariant::Array` for how much boilerplate that is).
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident};
+
+/// Derives `VariantType` for a struct with named fields.
+///
+/// By default, fields are encoded in declaration order as a D-Bus struct `(...)`, 8-byte
+/// aligned -- round-tripping through [`zvariant::Structure`] when converting to/from the dynamic
+/// `Variant` enum, since there's no dedicated `Variant` arm per derived type. Add
+/// `#[variant_type(signature = "dict")]` on the struct to instead encode it as an `a{sv}` vardict
+/// keyed by field name -- the common pattern for optional, forwards-compatible message payloads
+/// -- round-tripping through `zvariant::Dict` instead.
+///
+/// [`zvariant::Structure`]: ../zvariant/struct.Structure.html
+#[proc_macro_derive(VariantType, attributes(variant_type))]
+pub fn derive_variant_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    TokenStream::from(expand(&input).unwrap_or_else(|err| err.to_compile_error()))
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = named_fields(input)?;
+
+    if is_dict(input)? {
+        expand_dict(input, fields)
+    } else {
+        expand_struct(input, fields)
+    }
+}
+
+fn named_fields(
+    input: &DeriveInput,
+) -> syn::Result<&syn::punctuated::Punctuated<syn::Field, syn::Token![,]>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(&fields.named),
+            _ => Err(syn::Error::new_spanned(
+                input,
+                "VariantType can only be derived for structs with named fields",
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            input,
+            "VariantType can only be derived for structs with named fields",
+        )),
+    }
+}
+
+fn is_dict(input: &DeriveInput) -> syn::Result<bool> {
+    for attr in &input.attrs {
+        if !attr.path.is_ident("variant_type") {
+            continue;
+        }
+
+        let mut dict = false;
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("signature") {
+                let value = meta.value()?;
+                let signature: syn::LitStr = value.parse()?;
+                dict = signature.value() == "dict";
+
+                Ok(())
+            } else {
+                Err(meta.error("unsupported variant_type attribute"))
+            }
+        })?;
+
+        if dict {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+// Walks the fields once and emits the matching `encode_into`/`slice_data`/`decode` call per
+// field, threading the child `EncodingContext` through -- the same shape as a packet-description
+// code generator emitting one serialize/parse call per wire field.
+fn expand_struct(
+    input: &DeriveInput,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::Token![,]>,
+) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let idents: Vec<&Ident> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let types: Vec<&syn::Type> = fields.iter().map(|f| &f.ty).collect();
+
+    // `self.#ident` is a concrete `VariantType` (`u32`, `String`, a nested derived struct, ...),
+    // not a `Variant` -- call the trait's own `encode_into` rather than `Variant::
+    // encode_value_into`, which only `Variant` itself has.
+    let encode_fields = idents.iter().zip(types.iter()).map(|(ident, ty)| {
+        quote! {
+            <#ty as ::zvariant::VariantType>::encode_into(&self.#ident, bytes, child_context);
+        }
+    });
+
+    // Each field's own `slice_signature` peels its share off the members of the struct signature
+    // this call was given, instead of recomputing it from the type alone. A container-typed field
+    // (`Array`/`Dict`/`Maybe`) still can't be decoded: its `signature_str()` is just the
+    // container's own char (e.g. `"a"`), so `Self::signature_str()` is incomplete, and
+    // `ensure_correct_signature` accepts only that incomplete signature.
+    let decode_fields = idents.iter().zip(types.iter()).map(|(ident, ty)| {
+        quote! {
+            let field_signature = <#ty as ::zvariant::VariantType>::slice_signature(members)?;
+            let field_slice = <#ty as ::zvariant::VariantType>::slice_data(
+                &data.tail(extracted),
+                field_signature,
+                child_context,
+            )?;
+            extracted += field_slice.len();
+            let #ident = <#ty as ::zvariant::VariantType>::decode(
+                &field_slice,
+                field_signature,
+                child_context,
+            )?;
+            members = &members[field_signature.len()..];
+        }
+    });
+
+    let to_variant_fields = idents.iter().map(|ident| {
+        quote! {
+            self.#ident.to_variant()
+        }
+    });
+
+    let from_fields = idents.iter().zip(types.iter()).map(|(ident, ty)| {
+        quote! {
+            let #ident = <#ty as ::zvariant::VariantType>::take_from_variant(
+                fields.next().ok_or(::zvariant::VariantError::InsufficientData)?,
+            )?;
+        }
+    });
+
+    Ok(quote! {
+        impl ::zvariant::VariantTypeConstants for #name {
+            const SIGNATURE_CHAR: char = '(';
+            const SIGNATURE_STR: &'static str = "(";
+            const ALIGNMENT: usize = 8;
+        }
+
+        impl ::zvariant::VariantType for #name {
+            fn signature_char() -> char {
+                Self::SIGNATURE_CHAR
+            }
+
+            // Built from each field type's `signature_str()` in declaration order, computed once
+            // and cached rather than rebuilt (and leaked to satisfy `&'static str`) on every
+            // call. A container-typed field contributes only its container char, so with such a
+            // field this is not a complete signature.
+            fn signature_str() -> &'static str {
+                static SIGNATURE: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+                SIGNATURE.get_or_init(|| {
+                    let mut signature = String::from("(");
+                    #(signature.push_str(<#types as ::zvariant::VariantType>::signature_str());)*
+                    signature.push(')');
+
+                    signature
+                })
+            }
+
+            fn alignment() -> usize {
+                Self::ALIGNMENT
+            }
+
+            fn encode_into(&self, bytes: &mut dyn ::zvariant::encoding_buf::EncodingBuf, context: ::zvariant::EncodingContext) {
+                Self::add_padding(bytes, context);
+
+                let child_context = context.copy_for_child();
+                #(#encode_fields)*
+            }
+
+            fn slice_data(
+                data: &::zvariant::SharedData,
+                signature: &str,
+                context: ::zvariant::EncodingContext,
+            ) -> Result<::zvariant::SharedData, ::zvariant::VariantError> {
+                Self::ensure_correct_signature(signature)?;
+
+                let padding = Self::padding(data.position(), context);
+                let mut extracted = padding;
+                let child_context = context.copy_for_child();
+                let mut members = &signature[1..signature.len() - 1];
+
+                #(#decode_fields)*
+
+                Ok(data.head(extracted))
+            }
+
+            fn decode(
+                data: &::zvariant::SharedData,
+                signature: &str,
+                context: ::zvariant::EncodingContext,
+            ) -> Result<Self, ::zvariant::VariantError> {
+                Self::ensure_correct_signature(signature)?;
+
+                let padding = Self::padding(data.position(), context);
+                let mut extracted = padding;
+                let child_context = context.copy_for_child();
+                let mut members = &signature[1..signature.len() - 1];
+
+                #(#decode_fields)*
+
+                let _ = extracted;
+                Ok(#name { #(#idents),* })
+            }
+
+            fn ensure_correct_signature(signature: &str) -> Result<(), ::zvariant::VariantError> {
+                if signature != Self::signature_str() {
+                    return Err(::zvariant::VariantError::IncorrectType);
+                }
+
+                Ok(())
+            }
+
+            fn signature<'b>(&'b self) -> std::borrow::Cow<'b, str> {
+                std::borrow::Cow::from(Self::signature_str())
+            }
+
+            fn slice_signature(signature: &str) -> Result<&str, ::zvariant::VariantError> {
+                ::zvariant::Structure::slice_signature(signature)
+            }
+
+            fn is(variant: &::zvariant::Variant) -> bool {
+                <::zvariant::Structure as ::zvariant::VariantType>::is(variant)
+            }
+
+            fn take_from_variant(variant: ::zvariant::Variant) -> Result<Self, ::zvariant::VariantError> {
+                let structure = <::zvariant::Structure as ::zvariant::VariantType>::take_from_variant(variant)?;
+                let mut fields = structure.into_fields().into_iter();
+
+                #(#from_fields)*
+
+                Ok(#name { #(#idents),* })
+            }
+
+            // A derived struct isn't stored as itself inside `Variant` (only the generic
+            // `Structure` it round-trips through is), so there's no `&Self` to hand back here.
+            // `take_from_variant` (which decodes a fresh, owned value) is the real conversion;
+            // this is kept total rather than panicking.
+            fn from_variant(variant: &::zvariant::Variant) -> Result<&Self, ::zvariant::VariantError> {
+                let _ = variant;
+                Err(::zvariant::VariantError::IncorrectType)
+            }
+
+            fn to_variant(self) -> ::zvariant::Variant {
+                let fields = vec![#(#to_variant_fields),*];
+
+                ::zvariant::Structure::new(fields).to_variant()
+            }
+        }
+    })
+}
+
+// The vardict mode: an `a{sv}` keyed by field name. Every field is optional on the wire (missing
+// keys fall back to `Default::default()`), which is the point of this mode -- adding a field
+// later doesn't break old messages.
+fn expand_dict(
+    input: &DeriveInput,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::Token![,]>,
+) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let idents: Vec<&Ident> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let keys: Vec<String> = idents.iter().map(|ident| ident.to_string()).collect();
+    let types: Vec<&syn::Type> = fields.iter().map(|f| &f.ty).collect();
+
+    let encode_entries: Vec<TokenStream2> = idents
+        .iter()
+        .zip(keys.iter())
+        .map(|(ident, key)| {
+            quote! {
+                entries.push((#key.to_string().to_variant(), self.#ident.clone().to_variant()));
+            }
+        })
+        .collect();
+
+    let decode_entries: Vec<TokenStream2> = idents
+        .iter()
+        .zip(keys.iter())
+        .zip(types.iter())
+        .map(|((ident, key), ty)| {
+            quote! {
+                let #ident = map
+                    .remove(#key)
+                    .map(<#ty as ::zvariant::VariantType>::take_from_variant)
+                    .transpose()?
+                    .unwrap_or_default();
+            }
+        })
+        .collect();
+
+    Ok(quote! {
+        impl ::zvariant::VariantTypeConstants for #name {
+            const SIGNATURE_CHAR: char = 'a';
+            const SIGNATURE_STR: &'static str = "a{sv}";
+            const ALIGNMENT: usize = 4;
+        }
+
+        impl ::zvariant::VariantType for #name {
+            fn signature_char() -> char {
+                Self::SIGNATURE_CHAR
+            }
+            fn signature_str() -> &'static str {
+                Self::SIGNATURE_STR
+            }
+            fn alignment() -> usize {
+                Self::ALIGNMENT
+            }
+
+            fn encode_into(&self, bytes: &mut dyn ::zvariant::encoding_buf::EncodingBuf, context: ::zvariant::EncodingContext) {
+                let mut entries: Vec<(::zvariant::Variant, ::zvariant::Variant)> = vec![];
+                #(#encode_entries)*
+
+                // `Dict::new_from_entries` alone infers each signature from the first entry, the
+                // same way `Array::from(Vec<T>)` does -- fine for a uniform `a{ss}`-style dict,
+                // but wrong here: a vardict's declared value type is always `v`, regardless of
+                // which concrete type each field happens to hold, and mixed-type fields wouldn't
+                // even share one inferred value signature. Pin both signatures explicitly instead.
+                let key_signature = ::zvariant::Signature::new("s")
+                    .expect("\"s\" is always a valid signature");
+                let value_signature = ::zvariant::Signature::new("v")
+                    .expect("\"v\" is always a valid signature");
+
+                ::zvariant::Dict::new_from_entries(entries, key_signature, value_signature)
+                    .encode_into(bytes, context)
+            }
+
+            fn slice_data(
+                data: &::zvariant::SharedData,
+                signature: &str,
+                context: ::zvariant::EncodingContext,
+            ) -> Result<::zvariant::SharedData, ::zvariant::VariantError> {
+                ::zvariant::Dict::slice_data(data, signature, context)
+            }
+
+            fn decode(
+                data: &::zvariant::SharedData,
+                signature: &str,
+                context: ::zvariant::EncodingContext,
+            ) -> Result<Self, ::zvariant::VariantError> {
+                let dict = ::zvariant::Dict::decode(data, signature, context)?;
+                let mut map: std::collections::HashMap<String, ::zvariant::Variant> = dict.try_into()?;
+
+                #(#decode_entries)*
+
+                Ok(#name { #(#idents),* })
+            }
+
+            fn ensure_correct_signature(signature: &str) -> Result<(), ::zvariant::VariantError> {
+                if signature != Self::signature_str() {
+                    return Err(::zvariant::VariantError::IncorrectType);
+                }
+
+                Ok(())
+            }
+
+            fn signature<'b>(&'b self) -> std::borrow::Cow<'b, str> {
+                std::borrow::Cow::from(Self::signature_str())
+            }
+
+            fn slice_signature(signature: &str) -> Result<&str, ::zvariant::VariantError> {
+                ::zvariant::Dict::slice_signature(signature)
+            }
+
+            fn is(variant: &::zvariant::Variant) -> bool {
+                <::zvariant::Dict as ::zvariant::VariantType>::is(variant)
+            }
+
+            fn take_from_variant(variant: ::zvariant::Variant) -> Result<Self, ::zvariant::VariantError> {
+                let dict = <::zvariant::Dict as ::zvariant::VariantType>::take_from_variant(variant)?;
+                let mut map: std::collections::HashMap<String, ::zvariant::Variant> = dict.try_into()?;
+
+                #(#decode_entries)*
+
+                Ok(#name { #(#idents),* })
+            }
+
+            // Same reasoning as the struct mode: nothing stores `Self` inside `Variant` (only
+            // the `Dict` it round-trips through), so there's no `&Self` to return here.
+            fn from_variant(variant: &::zvariant::Variant) -> Result<&Self, ::zvariant::VariantError> {
+                let _ = variant;
+                Err(::zvariant::VariantError::IncorrectType)
+            }
+
+            fn to_variant(self) -> ::zvariant::Variant {
+                let mut entries: Vec<(::zvariant::Variant, ::zvariant::Variant)> = vec![];
+                #(#encode_entries)*
+
+                let key_signature = ::zvariant::Signature::new("s")
+                    .expect("\"s\" is always a valid signature");
+                let value_signature = ::zvariant::Signature::new("v")
+                    .expect("\"v\" is always a valid signature");
+
+                ::zvariant::Dict::new_from_entries(entries, key_signature, value_signature)
+                    .to_variant()
+            }
+        }
+    })
+}